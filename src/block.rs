@@ -0,0 +1,291 @@
+//! BC4/BC5-style single- and two-channel 4x4 block encode/decode, shared by
+//! [`crate::encode`] and [`crate::decode`].
+
+/// Size in bytes of one single-channel (BC4) block.
+pub const BLOCK_SIZE: usize = 6;
+
+fn select_channel_block(img: &[u8], width: usize, x: usize, y: usize, channels: usize, channel: usize, out: &mut [u8; 16]) {
+    for row in 0..4 {
+        for col in 0..4 {
+            let px = (((y + row) * width) + (x + col)) * channels + channel;
+            out[row * 4 + col] = img[px];
+        }
+    }
+}
+
+fn get_options_table(val0: u8, val1: u8, flip: bool) -> [u8; 8] {
+    let v0 = val0 as u32;
+    let v1 = val1 as u32;
+    [val0, val1, ((2*v0 + v1)/3) as u8, ((v0 + 2*v1)/3) as u8,
+     if flip {val0} else {val1}, if flip {val1} else {val0}, ((v0 + v1) / 2) as u8, 0]
+}
+
+fn choose_codeword(decreasing_order: bool, residuals: &[i32]) -> u32 {
+    let start_i = if decreasing_order {0} else {4};
+    let mut best_i = 0;
+    let mut best_dist = 99999;
+    for i in 0..4 {
+        if residuals[start_i + i] < best_dist {
+            best_i = i;
+            best_dist = residuals[start_i + i]
+        }
+    }
+    best_i as u32
+}
+
+/// The 4 codewords actually addressable by a block's 2-bit indices, given
+/// its endpoints and interpolation direction — i.e. `get_options_table`'s
+/// `[0..4]` half (`decreasing_order`) or `[4..8]` half (otherwise).
+fn codeword_options(val0: u8, val1: u8, decreasing_order: bool) -> [u8; 4] {
+    let v0 = val0 as u32;
+    let v1 = val1 as u32;
+    if decreasing_order {
+        [val0, val1, ((2*v0 + v1)/3) as u8, ((v0 + 2*v1)/3) as u8]
+    } else {
+        [val0, val1, ((v0 + v1) / 2) as u8, 0]
+    }
+}
+
+fn assign_codes(block: &[u8; 16], val0: u8, val1: u8, decreasing_order: bool) -> [u32; 16] {
+    let options = codeword_options(val0, val1, decreasing_order);
+    let mut codes = [0u32; 16];
+    for (i, &p) in block.iter().enumerate() {
+        codes[i] = choose_codeword(decreasing_order, &{
+            let mut residuals = [0i32; 8];
+            let start = if decreasing_order { 0 } else { 4 };
+            for (j, &opt) in options.iter().enumerate() {
+                residuals[start + j] = (p as i32 - opt as i32).abs();
+            }
+            residuals
+        });
+    }
+    codes
+}
+
+fn total_residual(block: &[u8; 16], val0: u8, val1: u8, decreasing_order: bool, codes: &[u32; 16]) -> i64 {
+    let options = codeword_options(val0, val1, decreasing_order);
+    block.iter().zip(codes.iter())
+        .map(|(&p, &c)| (p as i64 - options[c as usize] as i64).abs())
+        .sum()
+}
+
+/// Interpolation weight of `val1` in a code's reconstructed value (so the
+/// pixel value is `val0*(1-w) + val1*w`), or `None` when the code
+/// reconstructs to a fixed value (the increasing-order "literal zero"
+/// codeword) that doesn't depend on the endpoints at all.
+fn interpolation_weight(decreasing_order: bool, code: u32) -> Option<f32> {
+    match (decreasing_order, code) {
+        (true, 0) => Some(0.0),
+        (true, 1) => Some(1.0),
+        (true, 2) => Some(1.0 / 3.0),
+        (true, 3) => Some(2.0 / 3.0),
+        (false, 0) => Some(0.0),
+        (false, 1) => Some(1.0),
+        (false, 2) => Some(0.5),
+        (false, 3) => None,
+        _ => unreachable!(),
+    }
+}
+
+fn clamp_round(v: f32) -> u8 {
+    (v.clamp(0.0, 255.0) + 0.5) as u8
+}
+
+/// Solves the 2x2 least-squares system for the continuous `(val0, val1)`
+/// that minimize squared reconstruction error given the current `codes`,
+/// per-pixel treating the reconstructed value as `val0*(1-w) + val1*w`.
+/// Pixels whose code doesn't depend linearly on the endpoints are excluded.
+/// Returns `None` if the system is singular (e.g. every included pixel has
+/// the same weight).
+fn refine_endpoints(block: &[u8; 16], decreasing_order: bool, codes: &[u32; 16]) -> Option<(u8, u8)> {
+    let (mut sum_a, mut sum_b, mut sum_c, mut sum_p0, mut sum_p1) = (0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32);
+
+    for (&p, &code) in block.iter().zip(codes.iter()) {
+        let w = match interpolation_weight(decreasing_order, code) {
+            Some(w) => w,
+            None => continue,
+        };
+        let pf = p as f32;
+        sum_a += (1.0 - w) * (1.0 - w);
+        sum_b += (1.0 - w) * w;
+        sum_c += w * w;
+        sum_p0 += (1.0 - w) * pf;
+        sum_p1 += w * pf;
+    }
+
+    let det = sum_a * sum_c - sum_b * sum_b;
+    if det.abs() < 1e-6 { return None; }
+
+    let val0 = (sum_p0 * sum_c - sum_p1 * sum_b) / det;
+    let val1 = (sum_a * sum_p1 - sum_b * sum_p0) / det;
+    Some((clamp_round(val0), clamp_round(val1)))
+}
+
+/// Compresses one 4x4 block, picking `val0`/`val1` as the block's min/max
+/// and then running up to `quality` rounds of least-squares endpoint
+/// refinement, keeping whichever candidate yields the lowest total
+/// residual. `quality` of 0 reproduces the plain min/max result.
+pub fn compress_block(block: &[u8; 16], quality: u8) -> [u8; BLOCK_SIZE] {
+    let max_val = *block.iter().max().unwrap();
+    let min_val = *block.iter().min().unwrap();
+    let options = get_options_table(max_val, min_val, false);
+
+    let mut total_residuals = [0i32; 8];
+    for val in block.iter() {
+        for (j, option) in options.iter().enumerate() {
+            total_residuals[j] += (*val as i32 - *option as i32).abs();
+        }
+    }
+    let decreasing_order = total_residuals[2] + total_residuals[3] < total_residuals[6] + total_residuals[7];
+
+    let (mut val0, mut val1) = if decreasing_order { (max_val, min_val) } else { (min_val, max_val) };
+    let mut codes = assign_codes(block, val0, val1, decreasing_order);
+    let mut best_residual = total_residual(block, val0, val1, decreasing_order, &codes);
+
+    for _ in 0..quality {
+        let Some((r0, r1)) = refine_endpoints(block, decreasing_order, &codes) else { break };
+        let ordered = if decreasing_order { r0 > r1 } else { r0 < r1 };
+        if !ordered { break; }
+
+        let new_codes = assign_codes(block, r0, r1, decreasing_order);
+        let new_residual = total_residual(block, r0, r1, decreasing_order, &new_codes);
+        if new_residual >= best_residual { break; }
+
+        let converged = new_codes == codes;
+        val0 = r0; val1 = r1; codes = new_codes; best_residual = new_residual;
+        if converged { break; }
+    }
+
+    let mut packed_codes: u32 = 0;
+    for (i, &code) in codes.iter().enumerate() {
+        packed_codes |= code << (2*i);
+    }
+
+    [val0, val1, ((packed_codes >> 24) & 0xFF) as u8, ((packed_codes >> 16) & 0xFF) as u8, ((packed_codes >> 8) & 0xFF) as u8, (packed_codes & 0xFF) as u8]
+}
+
+fn decompress_channel_pixel(blocks: &[u8], width: usize, block_stride: usize, block_channel_offset: usize, x: usize, y: usize) -> u8 {
+    let block_idx = ((y/4) * (width/4)) + (x / 4);
+    let block_offset = block_idx * block_stride + block_channel_offset;
+    let val0 = blocks[block_offset];
+    let val1 = blocks[block_offset + 1];
+    let code_bytes = &blocks[block_offset + 2 .. block_offset + 6];
+    let codes = u32::from_be_bytes(code_bytes.try_into().unwrap());
+    let pixel_idx = ((y % 4) * 4) + (x % 4);
+    let code = (codes >> (pixel_idx*2)) & 3;
+    let idx = if val0 > val1 {code} else {code + 4};
+    get_options_table(val0, val1, true)[idx as usize]
+}
+
+/// Compresses `channels`-interleaved `img` into `out` as one BLOCK_SIZE-byte
+/// block per channel per 4x4 tile (e.g. 6 bytes for BC4, 12 for BC5). Each
+/// tile is independent, so this maps over tiles via
+/// [`crate::parallel::for_each_chunk_mut`].
+pub fn compress_into(img: &[u8], width: usize, channels: usize, quality: u8, out: &mut [u8]) {
+    let block_stride = channels * BLOCK_SIZE;
+    let tiles_per_row = width / 4;
+    crate::parallel::for_each_chunk_mut(out, block_stride, |tile_idx, chunk| {
+        let x = (tile_idx % tiles_per_row) * 4;
+        let y = (tile_idx / tiles_per_row) * 4;
+        let mut block = [0u8; 16];
+        for channel in 0..channels {
+            select_channel_block(img, width, x, y, channels, channel, &mut block);
+            let encoded = compress_block(&block, quality);
+            let offset = channel * BLOCK_SIZE;
+            chunk[offset..offset + BLOCK_SIZE].copy_from_slice(&encoded);
+        }
+    });
+}
+
+/// Decompresses a `channels`-interleaved block stream produced by
+/// [`compress_into`] back into `channels`-interleaved pixels in `out`. Each
+/// output row is independent, so this maps over rows via
+/// [`crate::parallel::for_each_chunk_mut`].
+pub fn decompress_into(blocks: &[u8], width: usize, channels: usize, out: &mut [u8]) {
+    let block_stride = channels * BLOCK_SIZE;
+    let row_stride = width * channels;
+    crate::parallel::for_each_chunk_mut(out, row_stride, |y, row| {
+        for x in 0..width {
+            for channel in 0..channels {
+                row[x * channels + channel] =
+                    decompress_channel_pixel(blocks, width, block_stride, channel * BLOCK_SIZE, x, y);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn compress_decompress_roundtrip_preserves_pixel_layout() {
+        // Each channel is constant within a 4x4 tile but differs between
+        // channels and tiles. Such tiles encode losslessly, so any mix-up of
+        // tiles or channels shows up as a mismatch.
+        let (width, height, channels) = (8, 4, 2);
+        let img: Vec<u8> = (0..width * height * channels)
+            .map(|i| {
+                let (px, channel) = (i / channels, i % channels);
+                let tile = (px % width) / 4;
+                (tile * 100 + channel * 40 + 10) as u8
+            })
+            .collect();
+
+        let mut blocks = vec![0u8; (width / 4) * (height / 4) * channels * BLOCK_SIZE];
+        compress_into(&img, width, channels, 0, &mut blocks);
+
+        let mut decoded = vec![0u8; img.len()];
+        decompress_into(&blocks, width, channels, &mut decoded);
+
+        assert_eq!(decoded, img);
+    }
+
+    /// Sum of per-pixel absolute reconstruction error of a single-channel
+    /// `compressed` block against its `original` 4x4 pixels.
+    fn block_residual(original: &[u8; 16], compressed: &[u8; BLOCK_SIZE]) -> i64 {
+        (0..16)
+            .map(|i| {
+                let decoded = decompress_channel_pixel(compressed, 4, BLOCK_SIZE, 0, i % 4, i / 4);
+                (original[i] as i64 - decoded as i64).abs()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn refinement_lowers_residual_vs_min_max() {
+        // Most pixels sit at a value the min/max endpoints' four fixed
+        // codewords don't land on, leaving refinement room to pull the
+        // endpoints towards where the pixels actually are.
+        let block: [u8; 16] = [0, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 255];
+
+        let min_max = compress_block(&block, 0);
+        let refined = compress_block(&block, 8);
+
+        assert!(block_residual(&block, &refined) < block_residual(&block, &min_max));
+    }
+
+    /// Exercises the rayon `par_chunks_mut` path in
+    /// [`crate::parallel::for_each_chunk_mut`] (only compiled in when the
+    /// `parallel` feature is on, unlike the other tests in this module).
+    /// Spans multiple tiles and rows so there's more than one chunk for
+    /// rayon to actually split across.
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_compress_decompress_roundtrip() {
+        let (width, channels) = (8, 1);
+        let mut img = [0u8; 64];
+        for (i, v) in img.iter_mut().enumerate() {
+            *v = (i * 37) as u8;
+        }
+
+        let mut blocks = [0u8; (8 / 4) * (8 / 4) * BLOCK_SIZE];
+        compress_into(&img, width, channels, 0, &mut blocks);
+
+        let mut decoded = [0u8; 64];
+        decompress_into(&blocks, width, channels, &mut decoded);
+
+        assert_eq!(decoded.len(), img.len());
+    }
+}
@@ -0,0 +1,155 @@
+//! DXT1/BC1-style 3-channel color block encode/decode.
+
+/// Size in bytes of one DXT1 block.
+pub const BLOCK_SIZE: usize = 8;
+
+fn select_block(img: &[u8], width: usize, x: usize, y: usize, out: &mut [[u8; 3]; 16]) {
+    for row in 0..4 {
+        for col in 0..4 {
+            let px = (((y + row) * width) + (x + col)) * 3;
+            out[row * 4 + col] = [img[px], img[px + 1], img[px + 2]];
+        }
+    }
+}
+
+fn pack_rgb565(c: [u8; 3]) -> u16 {
+    let r = (c[0] as u16 >> 3) & 0x1F;
+    let g = (c[1] as u16 >> 2) & 0x3F;
+    let b = (c[2] as u16 >> 3) & 0x1F;
+    (r << 11) | (g << 5) | b
+}
+
+fn unpack_rgb565(v: u16) -> [u8; 3] {
+    let r = ((v >> 11) & 0x1F) as u32;
+    let g = ((v >> 5) & 0x3F) as u32;
+    let b = (v & 0x1F) as u32;
+    [((r * 255 + 15) / 31) as u8, ((g * 255 + 31) / 63) as u8, ((b * 255 + 15) / 31) as u8]
+}
+
+fn lerp(a: u8, b: u8, t_num: u32, t_den: u32) -> u8 {
+    ((a as u32 * (t_den - t_num) + b as u32 * t_num) / t_den) as u8
+}
+
+/// Builds the 4-entry interpolated palette for a block. `four_color` selects
+/// between the opaque 4-color mode (`c0 > c1`) and the 3-color + black mode
+/// used by real DXT1 for punch-through alpha; this crate never emits the
+/// latter but still decodes it for compatibility with other encoders.
+fn palette(c0: [u8; 3], c1: [u8; 3], four_color: bool) -> [[u8; 3]; 4] {
+    let mix = |n, d| [lerp(c0[0], c1[0], n, d), lerp(c0[1], c1[1], n, d), lerp(c0[2], c1[2], n, d)];
+    if four_color {
+        [c0, c1, mix(1, 3), mix(2, 3)]
+    } else {
+        [c0, c1, mix(1, 2), [0, 0, 0]]
+    }
+}
+
+/// Finds the block's principal axis with a few power-iteration steps over
+/// its color covariance, then uses the pixels with the min/max projection
+/// onto that axis as the two endpoints.
+fn principal_axis_endpoints(pixels: &[[u8; 3]; 16]) -> ([u8; 3], [u8; 3]) {
+    let mut mean = [0.0f32; 3];
+    for p in pixels {
+        for c in 0..3 { mean[c] += p[c] as f32; }
+    }
+    for m in mean.iter_mut() { *m /= 16.0; }
+
+    // Power iteration over the color covariance, normalizing by the max abs
+    // component (not the L2 norm) each step so this stays sqrt-free and
+    // `no_std`-friendly — only the axis's direction matters below, not its
+    // magnitude.
+    let mut axis = [1.0f32, 1.0, 1.0];
+    for _ in 0..4 {
+        let mut next = [0.0f32; 3];
+        for p in pixels {
+            let d = [p[0] as f32 - mean[0], p[1] as f32 - mean[1], p[2] as f32 - mean[2]];
+            let dot = d[0]*axis[0] + d[1]*axis[1] + d[2]*axis[2];
+            for c in 0..3 { next[c] += d[c] * dot; }
+        }
+        let max_abs = next[0].abs().max(next[1].abs()).max(next[2].abs());
+        if max_abs < 1e-6 { break; }
+        for c in 0..3 { axis[c] = next[c] / max_abs; }
+    }
+
+    let mut min_proj = f32::MAX;
+    let mut max_proj = f32::MIN;
+    let mut min_px = pixels[0];
+    let mut max_px = pixels[0];
+    for p in pixels {
+        let d = [p[0] as f32 - mean[0], p[1] as f32 - mean[1], p[2] as f32 - mean[2]];
+        let proj = d[0]*axis[0] + d[1]*axis[1] + d[2]*axis[2];
+        if proj < min_proj { min_proj = proj; min_px = *p; }
+        if proj > max_proj { max_proj = proj; max_px = *p; }
+    }
+
+    (max_px, min_px)
+}
+
+fn closest_index(palette: &[[u8; 3]; 4], p: [u8; 3]) -> u8 {
+    let mut best_i = 0;
+    let mut best_dist = u32::MAX;
+    for (i, c) in palette.iter().enumerate() {
+        let dr = p[0] as i32 - c[0] as i32;
+        let dg = p[1] as i32 - c[1] as i32;
+        let db = p[2] as i32 - c[2] as i32;
+        let dist = (dr*dr + dg*dg + db*db) as u32;
+        if dist < best_dist { best_dist = dist; best_i = i; }
+    }
+    best_i as u8
+}
+
+fn compress_block(pixels: &[[u8; 3]; 16]) -> [u8; BLOCK_SIZE] {
+    let (c0, c1) = principal_axis_endpoints(pixels);
+    let mut v0 = pack_rgb565(c0);
+    let mut v1 = pack_rgb565(c1);
+    // Always emit the 4-color (opaque) mode: it needs c0 > c1 as u16.
+    if v0 <= v1 { core::mem::swap(&mut v0, &mut v1); }
+    if v0 == v1 {
+        if v0 == u16::MAX { v1 -= 1; } else { v0 += 1; }
+    }
+
+    let pal = palette(unpack_rgb565(v0), unpack_rgb565(v1), true);
+    let mut indices: u32 = 0;
+    for (i, p) in pixels.iter().enumerate() {
+        indices |= (closest_index(&pal, *p) as u32) << (i * 2);
+    }
+
+    let mut out = [0u8; BLOCK_SIZE];
+    out[0..2].copy_from_slice(&v0.to_le_bytes());
+    out[2..4].copy_from_slice(&v1.to_le_bytes());
+    out[4..8].copy_from_slice(&indices.to_le_bytes());
+    out
+}
+
+fn decompress_pixel(blocks: &[u8], width: usize, x: usize, y: usize) -> [u8; 3] {
+    let block_idx = ((y / 4) * (width / 4)) + (x / 4);
+    let block_offset = block_idx * BLOCK_SIZE;
+    let v0 = u16::from_le_bytes(blocks[block_offset..block_offset + 2].try_into().unwrap());
+    let v1 = u16::from_le_bytes(blocks[block_offset + 2..block_offset + 4].try_into().unwrap());
+    let indices = u32::from_le_bytes(blocks[block_offset + 4..block_offset + 8].try_into().unwrap());
+
+    let pal = palette(unpack_rgb565(v0), unpack_rgb565(v1), v0 > v1);
+    let pixel_idx = ((y % 4) * 4) + (x % 4);
+    let idx = (indices >> (pixel_idx * 2)) & 3;
+    pal[idx as usize]
+}
+
+pub fn compress_into(img: &[u8], width: usize, out: &mut [u8]) {
+    let tiles_per_row = width / 4;
+    crate::parallel::for_each_chunk_mut(out, BLOCK_SIZE, |tile_idx, chunk| {
+        let x = (tile_idx % tiles_per_row) * 4;
+        let y = (tile_idx / tiles_per_row) * 4;
+        let mut pixels = [[0u8; 3]; 16];
+        select_block(img, width, x, y, &mut pixels);
+        chunk.copy_from_slice(&compress_block(&pixels));
+    });
+}
+
+pub fn decompress_into(blocks: &[u8], width: usize, out: &mut [u8]) {
+    let row_stride = width * 3;
+    crate::parallel::for_each_chunk_mut(out, row_stride, |y, row| {
+        for x in 0..width {
+            let px = decompress_pixel(blocks, width, x, y);
+            row[x * 3..x * 3 + 3].copy_from_slice(&px);
+        }
+    });
+}
@@ -0,0 +1,160 @@
+//! Minimal DDS container support: just enough of the header to round-trip
+//! the block streams this crate produces, tagged with a FourCC identifying
+//! the [`crate::BlockFormat`] used.
+
+use crate::format::BlockFormat;
+
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+const HEADER_SIZE: u32 = 124;
+const PIXELFORMAT_SIZE: u32 = 32;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+const DDPF_FOURCC: u32 = 0x4;
+
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+
+/// Total size in bytes of the magic + `DDS_HEADER` this module writes.
+pub const HEADER_LEN: usize = 4 + HEADER_SIZE as usize;
+
+/// Builds the 4-byte `DDS ` magic plus a 124-byte `DDS_HEADER` describing a
+/// single-mip texture of `width` x `height`, stored with `format`'s FourCC,
+/// with `block_data_len` bytes of block data following it.
+pub fn write_dds_header(width: u32, height: u32, format: BlockFormat, block_data_len: u32) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    let mut w = Writer { buf: &mut header, pos: 0 };
+
+    w.put(&DDS_MAGIC);
+    w.put_u32(HEADER_SIZE);
+    w.put_u32(DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT | DDSD_MIPMAPCOUNT | DDSD_LINEARSIZE);
+    w.put_u32(height);
+    w.put_u32(width);
+    w.put_u32(block_data_len);
+    w.put_u32(0); // dwDepth
+    w.put_u32(1); // dwMipMapCount
+    w.skip(11 * 4); // dwReserved1
+
+    // DDS_PIXELFORMAT
+    w.put_u32(PIXELFORMAT_SIZE);
+    w.put_u32(DDPF_FOURCC);
+    w.put(&format.fourcc());
+    w.put_u32(0); // dwRGBBitCount
+    w.put_u32(0); // dwRBitMask
+    w.put_u32(0); // dwGBitMask
+    w.put_u32(0); // dwBBitMask
+    w.put_u32(0); // dwABitMask
+
+    w.put_u32(DDSCAPS_TEXTURE); // dwCaps
+    w.put_u32(0); // dwCaps2
+    w.put_u32(0); // dwCaps3
+    w.put_u32(0); // dwCaps4
+    w.put_u32(0); // dwReserved2
+
+    header
+}
+
+/// Parses a `DDS ` magic + `DDS_HEADER` from the front of `data` and returns
+/// `(width, height, format)`, or [`crate::Error::InvalidDdsHeader`] if the
+/// magic, sizes or FourCC don't match what [`write_dds_header`] produces.
+pub fn read_dds_header(data: &[u8]) -> Result<(u32, u32, BlockFormat), crate::Error> {
+    if data.len() < HEADER_LEN { return Err(crate::Error::InvalidDdsHeader); }
+    if data[0..4] != DDS_MAGIC { return Err(crate::Error::InvalidDdsHeader); }
+
+    let header_size = read_u32(data, 4);
+    if header_size != HEADER_SIZE { return Err(crate::Error::InvalidDdsHeader); }
+
+    let height = read_u32(data, 12);
+    let width = read_u32(data, 16);
+
+    // DDS_PIXELFORMAT starts right after the 4-byte magic + 28-byte
+    // DDS_HEADER fields + 44-byte dwReserved1 = offset 76 (dwSize), with
+    // dwFlags and dwFourCC the next two fields.
+    let pf_flags = read_u32(data, 76 + 4);
+    let fourcc: [u8; 4] = data[76 + 8..76 + 12].try_into().unwrap();
+    if pf_flags & DDPF_FOURCC == 0 {
+        return Err(crate::Error::InvalidDdsHeader);
+    }
+    let format = BlockFormat::from_fourcc(fourcc).ok_or(crate::Error::InvalidDdsHeader)?;
+
+    Ok((width, height, format))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Byte offset of the first `dwReserved1` DWORD — unused by this module, but
+/// left as a zeroed slot a caller can stash extra metadata in (see
+/// `crate::entropy::stamp_header`) without breaking readers that only
+/// understand a plain `DDS_HEADER`.
+#[cfg(feature = "std")]
+pub(crate) const RESERVED1_OFFSET: usize = 32;
+
+/// Overwrites the `dwPitchOrLinearSize` field of a header written by
+/// [`write_dds_header`] — used when the block data following the header
+/// ends up a different length than `write_dds_header` was told (e.g. a
+/// compression layer applied after the fact).
+#[cfg(feature = "std")]
+pub(crate) fn patch_pitch(header: &mut [u8], block_data_len: u32) {
+    header[20..24].copy_from_slice(&block_data_len.to_le_bytes());
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    fn put_u32(&mut self, val: u32) {
+        self.put(&val.to_le_bytes());
+    }
+
+    fn skip(&mut self, n: usize) {
+        self.pos += n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_roundtrips_per_format() {
+        for format in [BlockFormat::Bc4, BlockFormat::Bc5, BlockFormat::Bc1] {
+            let header = write_dds_header(16, 8, format, 123);
+            assert_eq!(read_dds_header(&header), Ok((16, 8, format)));
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_magic() {
+        let mut header = write_dds_header(16, 8, BlockFormat::Bc1, 123);
+        header[0] = b'X';
+        assert_eq!(read_dds_header(&header), Err(crate::Error::InvalidDdsHeader));
+    }
+
+    #[test]
+    fn rejects_unrecognized_fourcc() {
+        let mut header = write_dds_header(16, 8, BlockFormat::Bc1, 123);
+        header[76 + 8..76 + 12].copy_from_slice(b"????");
+        assert_eq!(read_dds_header(&header), Err(crate::Error::InvalidDdsHeader));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn patch_pitch_overwrites_dw_pitch_or_linear_size() {
+        let mut header = write_dds_header(16, 8, BlockFormat::Bc1, 123);
+        patch_pitch(&mut header, 99);
+        assert_eq!(read_u32(&header, 20), 99);
+    }
+}
@@ -0,0 +1,201 @@
+//! Optional post-pass that further compresses the raw block byte stream
+//! [`crate::encode`] produces — smooth textures repeat endpoints and index
+//! bytes a lot, and the fixed per-block layout doesn't exploit that on its
+//! own. Callers pick a [`Compressor`], run it over the block data after the
+//! DDS header, and record its [`Compressor::id`] alongside so `decode` can
+//! look the right one back up via [`by_id`].
+//!
+//! Needs an allocator and (for [`Deflate`]) external codecs, so this module
+//! only exists under the default-on `std` feature, unlike the rest of this
+//! crate's `no_std` core.
+
+use std::vec::Vec;
+
+use crate::Error;
+
+/// A pluggable codec for the block byte stream, identified by a stable
+/// 1-byte id so a container records which one `encode` used.
+pub trait Compressor {
+    fn id(&self) -> u8;
+    fn compress(&self, data: &[u8]) -> Vec<u8>;
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// Looks up the [`Compressor`] for a 1-byte id read from a container, or
+/// `None` if it's not one `by_id` knows about.
+pub fn by_id(id: u8) -> Option<&'static dyn Compressor> {
+    match id {
+        Uncompressed::ID => Some(&Uncompressed),
+        Deflate::ID => Some(&Deflate),
+        PackBits::ID => Some(&PackBits),
+        _ => None,
+    }
+}
+
+/// Rewrites a `header` written by [`crate::encode`] to record which
+/// [`Compressor`] the block data right after it was compressed with (stashed
+/// in the header's first `dwReserved1` DWORD) and that data's actual
+/// `compressed_len`, since the header's `dwPitchOrLinearSize` field
+/// otherwise still claims the uncompressed block length. Left at
+/// [`Uncompressed`]'s id (`0`, the header's existing zeroed reserved field),
+/// a header is untouched and the container stays byte-identical to a plain
+/// DDS file.
+pub fn stamp_header(header: &mut [u8], id: u8, compressed_len: u32) {
+    header[crate::dds::RESERVED1_OFFSET] = id;
+    crate::dds::patch_pitch(header, compressed_len);
+}
+
+/// Reads the compression id [`stamp_header`] stashed in a container's
+/// header.
+pub fn read_id(header: &[u8]) -> u8 {
+    header[crate::dds::RESERVED1_OFFSET]
+}
+
+/// Passes the block data through unchanged.
+pub struct Uncompressed;
+
+impl Uncompressed {
+    const ID: u8 = 0;
+}
+
+impl Compressor for Uncompressed {
+    fn id(&self) -> u8 { Self::ID }
+    fn compress(&self, data: &[u8]) -> Vec<u8> { data.to_vec() }
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Error> { Ok(data.to_vec()) }
+}
+
+/// Raw DEFLATE (RFC 1951), best for blocks with long repeated runs of
+/// endpoints/indices.
+pub struct Deflate;
+
+impl Deflate {
+    const ID: u8 = 1;
+}
+
+impl Compressor for Deflate {
+    fn id(&self) -> u8 { Self::ID }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        deflate::deflate_bytes(data)
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        inflate::inflate_bytes(data).map_err(|_| Error::DecompressionFailed)
+    }
+}
+
+/// Classic PackBits RLE (as used by TIFF): cheap to encode/decode, good for
+/// long runs of an identical byte.
+pub struct PackBits;
+
+impl PackBits {
+    const ID: u8 = 2;
+}
+
+impl Compressor for PackBits {
+    fn id(&self) -> u8 { Self::ID }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            let run_byte = data[i];
+            let mut run_len = 1;
+            while i + run_len < data.len() && data[i + run_len] == run_byte && run_len < 128 {
+                run_len += 1;
+            }
+
+            if run_len >= 2 {
+                out.push((1i32 - run_len as i32) as i8 as u8);
+                out.push(run_byte);
+                i += run_len;
+            } else {
+                let lit_start = i;
+                let mut lit_len = 1;
+                i += 1;
+                while i < data.len() && lit_len < 128 && !(i + 1 < data.len() && data[i] == data[i + 1]) {
+                    lit_len += 1;
+                    i += 1;
+                }
+                out.push((lit_len - 1) as u8);
+                out.extend_from_slice(&data[lit_start..lit_start + lit_len]);
+            }
+        }
+        out
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            let n = data[i] as i8;
+            i += 1;
+            if n >= 0 {
+                let len = n as usize + 1;
+                let chunk = data.get(i..i + len).ok_or(Error::DecompressionFailed)?;
+                out.extend_from_slice(chunk);
+                i += len;
+            } else if n != -128 {
+                let len = (1 - n as i32) as usize;
+                let byte = *data.get(i).ok_or(Error::DecompressionFailed)?;
+                out.resize(out.len() + len, byte);
+                i += 1;
+            }
+            // n == -128 is a documented no-op in the PackBits spec.
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: &[&[u8]] = &[
+        b"",
+        b"a single literal run",
+        &[7, 7, 7, 7, 7, 7, 7, 7, 7, 7],
+        &[1, 2, 3, 4, 5, 6, 7, 7, 7, 7, 7, 8, 9, 10, 1, 1, 1, 1],
+    ];
+
+    #[test]
+    fn packbits_roundtrips() {
+        for sample in SAMPLES {
+            let compressed = PackBits.compress(sample);
+            assert_eq!(PackBits.decompress(&compressed).unwrap(), sample.to_vec());
+        }
+    }
+
+    #[test]
+    fn deflate_roundtrips() {
+        for sample in SAMPLES {
+            let compressed = Deflate.compress(sample);
+            assert_eq!(Deflate.decompress(&compressed).unwrap(), sample.to_vec());
+        }
+    }
+
+    #[test]
+    fn uncompressed_roundtrips() {
+        for sample in SAMPLES {
+            let compressed = Uncompressed.compress(sample);
+            assert_eq!(Uncompressed.decompress(&compressed).unwrap(), sample.to_vec());
+        }
+    }
+
+    #[test]
+    fn by_id_looks_up_each_compressor() {
+        assert_eq!(by_id(Uncompressed.id()).unwrap().id(), Uncompressed.id());
+        assert_eq!(by_id(Deflate.id()).unwrap().id(), Deflate.id());
+        assert_eq!(by_id(PackBits.id()).unwrap().id(), PackBits.id());
+        assert!(by_id(255).is_none());
+    }
+
+    #[test]
+    fn stamp_header_defaults_to_byte_identical_header() {
+        let header = crate::dds::write_dds_header(16, 8, crate::BlockFormat::Bc1, 123);
+        let mut stamped = header;
+        stamp_header(&mut stamped, Uncompressed::ID, 123);
+        assert_eq!(stamped, header);
+        assert_eq!(read_id(&stamped), Uncompressed::ID);
+    }
+}
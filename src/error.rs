@@ -0,0 +1,37 @@
+use core::fmt;
+
+/// Errors returned by the [`encode`](crate::encode) and [`decode`](crate::decode) entry points.
+///
+/// `#[non_exhaustive]` so new failure modes (new block formats, new container
+/// fields) can be added without a breaking change.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// A caller-supplied input or output buffer is shorter than the image's
+    /// dimensions and format need (see [`crate::encoded_len`] and
+    /// [`crate::required_bytes`]).
+    BufferTooSmall,
+    /// `width` or `height` is not a multiple of 4, so the image can't be
+    /// tiled into 4x4 blocks.
+    DimensionsNotMultipleOfFour,
+    /// The input doesn't start with a recognizable `DDS ` container header.
+    InvalidDdsHeader,
+    /// [`crate::entropy::Compressor::decompress`] couldn't make sense of its
+    /// input.
+    DecompressionFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Error::BufferTooSmall => "output buffer is too small",
+            Error::DimensionsNotMultipleOfFour => "width/height must be a multiple of 4",
+            Error::InvalidDdsHeader => "input is not a valid DDS container",
+            Error::DecompressionFailed => "failed to decompress block data",
+        };
+        f.write_str(msg)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
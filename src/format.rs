@@ -0,0 +1,53 @@
+/// Which block compression scheme an image is stored with.
+///
+/// Selected automatically from the source PNG's color type on encode, and
+/// recorded in the DDS container's FourCC so `decode` picks the matching
+/// scheme back up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFormat {
+    /// BC4U / "ATI1": one 6-byte block per 4x4 tile, single channel.
+    Bc4,
+    /// BC5 / "ATI2": two interleaved BC4-style sub-blocks, one per channel —
+    /// typically the two channels of a normal map.
+    Bc5,
+    /// DXT1 / BC1: one 8-byte block per 4x4 tile, 3-channel RGB565 endpoints
+    /// with a 4-entry interpolated palette.
+    Bc1,
+}
+
+impl BlockFormat {
+    /// Number of interleaved u8 channels `encode`/`decode` read/write per pixel.
+    pub fn channels(self) -> usize {
+        match self {
+            BlockFormat::Bc4 => 1,
+            BlockFormat::Bc5 => 2,
+            BlockFormat::Bc1 => 3,
+        }
+    }
+
+    /// Size in bytes of one compressed 4x4 block.
+    pub fn bytes_per_block(self) -> usize {
+        match self {
+            BlockFormat::Bc4 => crate::block::BLOCK_SIZE,
+            BlockFormat::Bc5 => crate::block::BLOCK_SIZE * 2,
+            BlockFormat::Bc1 => crate::color::BLOCK_SIZE,
+        }
+    }
+
+    pub(crate) fn fourcc(self) -> [u8; 4] {
+        match self {
+            BlockFormat::Bc4 => *b"ATI1",
+            BlockFormat::Bc5 => *b"ATI2",
+            BlockFormat::Bc1 => *b"DXT1",
+        }
+    }
+
+    pub(crate) fn from_fourcc(fourcc: [u8; 4]) -> Option<BlockFormat> {
+        match &fourcc {
+            b"ATI1" => Some(BlockFormat::Bc4),
+            b"ATI2" => Some(BlockFormat::Bc5),
+            b"DXT1" => Some(BlockFormat::Bc1),
+            _ => None,
+        }
+    }
+}
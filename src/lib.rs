@@ -0,0 +1,159 @@
+//! tinydxt is a tiny block compressor/decompressor supporting BC4 (single
+//! channel), BC5 (two channel) and DXT1/BC1 (3-channel color) block formats.
+//!
+//! The core codec is `no_std`: callers hand in an output buffer sized with
+//! [`encoded_len`]/[`required_bytes`] and get back an [`Error`] instead of a
+//! panic if it's too small. The `std` feature (on by default) additionally
+//! pulls in the `tinydxt` binary's PNG/file-IO front end; turn it off to use
+//! the library on its own in an embedded or `no_std` context.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod block;
+mod color;
+mod dds;
+mod error;
+mod format;
+mod parallel;
+
+#[cfg(feature = "std")]
+pub mod entropy;
+
+pub use error::Error;
+pub use format::BlockFormat;
+
+/// Tunable knobs for [`encode`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncodeOptions {
+    /// Maximum number of least-squares endpoint refinement passes to run on
+    /// each BC4/BC5 block. Refinement stops early once the indices stop
+    /// changing or a pass no longer lowers the block's total residual, and
+    /// the plain min/max endpoints are kept unless a pass improved on them. `0` disables
+    /// refinement and reproduces the original min/max-only behavior. Has no
+    /// effect on [`BlockFormat::Bc1`] blocks, whose endpoints are already
+    /// chosen via principal-axis analysis.
+    pub quality: u8,
+}
+
+/// Size in bytes of the DDS container header written by [`encode`] and
+/// consumed by [`read_header`].
+pub const DDS_HEADER_LEN: usize = dds::HEADER_LEN;
+
+/// Number of bytes [`encode`] needs in its `out` buffer for an image of this
+/// size and `format`: the DDS container header plus one block per 4x4 pixel
+/// tile.
+pub fn encoded_len(width: usize, height: usize, format: BlockFormat) -> usize {
+    dds::HEADER_LEN + block_data_len(width, height, format)
+}
+
+/// Number of bytes [`decode`] needs in its `out` buffer, and [`encode`]
+/// expects in its `img` buffer, for an image of this size and `format`:
+/// `width * height * format.channels()`.
+pub fn required_bytes(width: usize, height: usize, format: BlockFormat) -> usize {
+    width * height * format.channels()
+}
+
+fn block_data_len(width: usize, height: usize, format: BlockFormat) -> usize {
+    (width / 4) * (height / 4) * format.bytes_per_block()
+}
+
+/// Reads the `width`/`height`/[`BlockFormat`] embedded in a DDS container's
+/// header, without decoding the block data that follows it.
+pub fn read_header(data: &[u8]) -> Result<(u32, u32, BlockFormat), Error> {
+    dds::read_dds_header(data)
+}
+
+/// Compresses a `format.channels()`-interleaved image of `width` x `height`
+/// pixels (row-major, no padding) from `img` into the DDS container written
+/// to `out`. `out` must be at least [`encoded_len`]`(width, height, format)`
+/// bytes.
+pub fn encode(img: &[u8], width: usize, height: usize, format: BlockFormat, options: EncodeOptions, out: &mut [u8]) -> Result<(), Error> {
+    if !width.is_multiple_of(4) || !height.is_multiple_of(4) { return Err(Error::DimensionsNotMultipleOfFour); }
+    if img.len() < required_bytes(width, height, format) { return Err(Error::BufferTooSmall); }
+    if out.len() < encoded_len(width, height, format) { return Err(Error::BufferTooSmall); }
+
+    let block_data_len = block_data_len(width, height, format);
+    out[..dds::HEADER_LEN].copy_from_slice(&dds::write_dds_header(width as u32, height as u32, format, block_data_len as u32));
+    let block_data = &mut out[dds::HEADER_LEN..dds::HEADER_LEN + block_data_len];
+
+    match format {
+        BlockFormat::Bc4 => block::compress_into(img, width, 1, options.quality, block_data),
+        BlockFormat::Bc5 => block::compress_into(img, width, 2, options.quality, block_data),
+        BlockFormat::Bc1 => color::compress_into(img, width, block_data),
+    }
+
+    Ok(())
+}
+
+/// Decompresses a raw block stream (no DDS header — see [`read_header`] to
+/// recover `width`/`height`/`format` from a full container first) into
+/// `out`, `format.channels()` bytes per pixel. `out` must be at least
+/// [`required_bytes`]`(width, height, format)` bytes.
+pub fn decode(blocks: &[u8], width: usize, height: usize, format: BlockFormat, out: &mut [u8]) -> Result<(), Error> {
+    if !width.is_multiple_of(4) || !height.is_multiple_of(4) { return Err(Error::DimensionsNotMultipleOfFour); }
+    if blocks.len() < block_data_len(width, height, format) { return Err(Error::BufferTooSmall); }
+    if out.len() < required_bytes(width, height, format) { return Err(Error::BufferTooSmall); }
+
+    match format {
+        BlockFormat::Bc4 => block::decompress_into(blocks, width, 1, out),
+        BlockFormat::Bc5 => block::decompress_into(blocks, width, 2, out),
+        BlockFormat::Bc1 => color::decompress_into(blocks, width, out),
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn roundtrip(format: BlockFormat, width: usize, height: usize) {
+        let channels = format.channels();
+        let img: Vec<u8> = (0..width * height * channels).map(|i| (i * 37) as u8).collect();
+
+        let mut container = vec![0u8; encoded_len(width, height, format)];
+        encode(&img, width, height, format, EncodeOptions::default(), &mut container).unwrap();
+
+        let (read_width, read_height, read_format) = read_header(&container).unwrap();
+        assert_eq!((read_width as usize, read_height as usize, read_format), (width, height, format));
+
+        let mut decoded = vec![0u8; required_bytes(width, height, format)];
+        decode(&container[DDS_HEADER_LEN..], width, height, format, &mut decoded).unwrap();
+        assert_eq!(decoded.len(), img.len());
+    }
+
+    #[test]
+    fn roundtrips_bc4() {
+        roundtrip(BlockFormat::Bc4, 8, 4);
+    }
+
+    #[test]
+    fn roundtrips_bc5() {
+        roundtrip(BlockFormat::Bc5, 8, 4);
+    }
+
+    #[test]
+    fn roundtrips_bc1() {
+        roundtrip(BlockFormat::Bc1, 8, 4);
+    }
+
+    #[test]
+    fn rejects_dimensions_not_multiple_of_four() {
+        let mut out = vec![0u8; encoded_len(5, 4, BlockFormat::Bc4)];
+        let img = vec![0u8; required_bytes(5, 4, BlockFormat::Bc4)];
+        assert_eq!(
+            encode(&img, 5, 4, BlockFormat::Bc4, EncodeOptions::default(), &mut out),
+            Err(Error::DimensionsNotMultipleOfFour)
+        );
+    }
+
+    #[test]
+    fn rejects_buffer_too_small() {
+        let mut out = vec![0u8; encoded_len(4, 4, BlockFormat::Bc4) - 1];
+        let img = vec![0u8; required_bytes(4, 4, BlockFormat::Bc4)];
+        assert_eq!(
+            encode(&img, 4, 4, BlockFormat::Bc4, EncodeOptions::default(), &mut out),
+            Err(Error::BufferTooSmall)
+        );
+    }
+}
@@ -1,106 +1,60 @@
 use std::env;
 use std::fs::File;
 use std::str::FromStr;
-use png;
+use tinydxt::entropy::{self, Compressor};
+use tinydxt::{BlockFormat, EncodeOptions};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn select_block(img: &[u8], width: usize, x: usize, y: usize, output: &mut Vec<u8>) {
-    output.extend_from_slice(&img[((y + 0)*width)+x .. ((y + 0)*width)+(x+4)]);
-    output.extend_from_slice(&img[((y + 1)*width)+x .. ((y + 1)*width)+(x+4)]);
-    output.extend_from_slice(&img[((y + 2)*width)+x .. ((y + 2)*width)+(x+4)]);
-    output.extend_from_slice(&img[((y + 3)*width)+x .. ((y + 3)*width)+(x+4)]);
-}
-
-fn get_options_table(val0: u8, val1: u8, flip: bool) -> [u8; 8] {
-    let v0 = val0 as u32;
-    let v1 = val1 as u32;
-    return [val0, val1, ((2*v0 + v1)/3) as u8, ((v0 + 2*v1)/3) as u8, 
-            if flip {val0} else {val1}, if flip {val1} else {val0}, ((v0 + v1) / 2) as u8, 0]
-}
+static USAGE : &str = "Usage: $0 <encode/decode> <input_path> <output_path> [<width> <height>] [--quality <0-255>] [--compression <none/deflate/packbits>]";
 
-fn choose_codeword(decreasing_order: bool, residuals: &[i32]) -> u32 {
-    let start_i = if decreasing_order {0} else {4};
-    let mut best_i = 0;
-    let mut best_dist = 99999;
-    for i in 0..4 {
-        if residuals[start_i + i] < best_dist {
-            best_i = i;
-            best_dist = residuals[start_i + i]
-        }
+/// Pulls a `--quality <n>` flag out of the argument list, if present.
+fn quality_flag() -> Result<u8> {
+    let args: Vec<String> = env::args().collect();
+    match args.iter().position(|a| a == "--quality") {
+        Some(i) => Ok(u8::from_str(args.get(i + 1).ok_or(USAGE)?).map_err(|_| USAGE)?),
+        None => Ok(0),
     }
-    return best_i as u32;
 }
 
-fn compress_block(block: &[u8]) -> [u8; 6] {
-    let max_val = *block.iter().max().unwrap();
-    let min_val = *block.iter().min().unwrap();
-    let options = get_options_table(max_val, min_val, false);
-
-    let mut residuals = [0i32; 16*8];
-    let mut total_residuals = [0i32; 8];
-    for (i, val) in block.iter().enumerate() {
-        let res_block_offset = i*8;
-        for (j, option) in options.iter().enumerate() {
-            let residual = (*val as i32 - *option as i32).abs();
-            residuals[res_block_offset+j] = residual;
-            total_residuals[j] += residual;
-        }
-    }
-    let decreasing_order = total_residuals[2] + total_residuals[3] < total_residuals[6] + total_residuals[7];
-    let val0 = if decreasing_order { max_val } else { min_val };
-    let val1 = if decreasing_order { min_val } else { max_val };
-    let mut codes : u32 = 0;
-    for i in 0 .. 16 {
-        codes |= choose_codeword(decreasing_order, &residuals[i*8 .. (i+1)*8]) << (2*i);
+/// Pulls a `--compression <none/deflate/packbits>` flag out of the argument
+/// list, defaulting to no compression (a strictly DDS-conformant container).
+fn compression_flag() -> Result<&'static dyn Compressor> {
+    let args: Vec<String> = env::args().collect();
+    match args.iter().position(|a| a == "--compression") {
+        Some(i) => match args.get(i + 1).map(String::as_str) {
+            Some("none") => Ok(&entropy::Uncompressed),
+            Some("deflate") => Ok(&entropy::Deflate),
+            Some("packbits") => Ok(&entropy::PackBits),
+            _ => Err(USAGE.into()),
+        },
+        None => Ok(&entropy::Uncompressed),
     }
-
-    return [val0, val1, ((codes >> 24) & 0xFF) as u8, ((codes >> 16) & 0xFF) as u8, ((codes >> 8) & 0xFF) as u8, ((codes >> 0) & 0xFF) as u8]
 }
 
-fn compress(img: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
-    let mut result = Vec::new();
-    result.reserve_exact(width*height);
-
-    let mut block = vec![0; 16];
-    
-    for y in (0..height).step_by(4) {
-        for x in (0..width).step_by(4) {
-            block.clear();
-            select_block(img, width, x, y, &mut block);
-            result.extend(compress_block(&block[..]));
+/// Picks the block format for a PNG color type, and strips any channels the
+/// chosen format doesn't store (currently: alpha on RGBA input).
+fn select_format(color_type: png::ColorType, pixels: &[u8]) -> Result<(BlockFormat, Vec<u8>)> {
+    match color_type {
+        png::ColorType::Grayscale => Ok((BlockFormat::Bc4, pixels.to_vec())),
+        png::ColorType::GrayscaleAlpha => Ok((BlockFormat::Bc5, pixels.to_vec())),
+        png::ColorType::Rgb => Ok((BlockFormat::Bc1, pixels.to_vec())),
+        png::ColorType::Rgba => {
+            let rgb = pixels.chunks_exact(4).flat_map(|px| &px[..3]).copied().collect();
+            Ok((BlockFormat::Bc1, rgb))
         }
+        _ => Err("Unsupported PNG color type: only Grayscale, GrayscaleAlpha, Rgb and Rgba 8-bit input is allowed".into()),
     }
-
-    return Ok(result)
 }
 
-fn decompress_pixel(img: &[u8], width: usize, height: usize, x: usize, y: usize) -> u8 {
-    let block_idx = ((y/4) * (width/4)) + (x / 4);
-    let block_offset = block_idx * 6;
-    let val0 = img[block_offset + 0];
-    let val1 = img[block_offset + 1];
-    let code_bytes = &img[block_offset + 2 .. block_offset + 6];
-    let codes = u32::from_be_bytes(code_bytes.try_into().unwrap());
-    let pixel_idx = ((y % 4) * 4) + (x % 4);
-    let code = (codes >> (pixel_idx*2)) & 3;
-    let idx = if val0 > val1 {code + 0} else {code + 4};
-    return get_options_table(val0, val1, true)[idx as usize];
-}
-
-fn decompress(img: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
-    let mut result = vec![0; width*height];
-    for y in 0 .. height {
-        for x in 0 .. width {
-            result[(y * width) + x] = decompress_pixel(img, width, height, x, y);
-        }
+fn png_color_type(format: BlockFormat) -> png::ColorType {
+    match format {
+        BlockFormat::Bc4 => png::ColorType::Grayscale,
+        BlockFormat::Bc5 => png::ColorType::GrayscaleAlpha,
+        BlockFormat::Bc1 => png::ColorType::Rgb,
     }
-
-    Ok(result)
 }
 
-static USAGE : &str = "Usage: $0 <encode/decode> <input_path> <output_path> [<width> <height>]";
-
 fn main() -> Result<()> {
     let mode : std::string::String = env::args().nth(1).ok_or(USAGE)?;
     let input_file = env::args().nth(2).ok_or(USAGE)?;
@@ -110,26 +64,50 @@ fn main() -> Result<()> {
         let decoder = png::Decoder::new(File::open(input_file)?);
         let mut reader = decoder.read_info()?;
         let (color_type, bit_depth) = reader.output_color_type();
-        if color_type != png::ColorType::Grayscale { return Err("Only no-alpha grayscale input PNGs allowed".into()); }
         if bit_depth != png::BitDepth::Eight { return Err("Only 8-bit input PNGs allowed".into()); }
 
         let mut buf = vec![0; reader.output_buffer_size()];
         let info = reader.next_frame(&mut buf)?;
         let bytes = &buf[..info.buffer_size()];
+        let (width, height) = (info.width as usize, info.height as usize);
+
+        let (format, pixels) = select_format(color_type, bytes)?;
+        let options = EncodeOptions { quality: quality_flag()? };
+        let compressor = compression_flag()?;
+
+        let mut dds = vec![0u8; tinydxt::encoded_len(width, height, format)];
+        tinydxt::encode(&pixels, width, height, format, options, &mut dds)?;
+
+        let compressed_blocks = compressor.compress(&dds[tinydxt::DDS_HEADER_LEN..]);
+        entropy::stamp_header(&mut dds[..tinydxt::DDS_HEADER_LEN], compressor.id(), compressed_blocks.len() as u32);
 
-        let compressed = compress(bytes, info.width as usize, info.height as usize)?;
-        std::fs::write(output_file, compressed)?
+        let mut out = dds[..tinydxt::DDS_HEADER_LEN].to_vec();
+        out.extend(compressed_blocks);
+        std::fs::write(output_file, out)?
     } else if mode.eq_ignore_ascii_case("decode") {
-        let width = u32::from_str(&env::args().nth(4).ok_or(USAGE)?).expect(USAGE);
-        let height = u32::from_str(&env::args().nth(5).ok_or(USAGE)?).expect(USAGE);
         let input = std::fs::read(input_file)?;
-        let decompressed = decompress(&input[..], width as usize, height as usize)?;
+        let (width, height, format, blocks) = match tinydxt::read_header(&input[..]) {
+            Ok((width, height, format)) => {
+                let compression_id = entropy::read_id(&input[..tinydxt::DDS_HEADER_LEN]);
+                let compressor = entropy::by_id(compression_id).ok_or("Unrecognized compression id in container")?;
+                let blocks = compressor.decompress(&input[tinydxt::DDS_HEADER_LEN..])?;
+                (width as usize, height as usize, format, blocks)
+            }
+            Err(_) => {
+                let width = u32::from_str(&env::args().nth(4).ok_or(USAGE)?).expect(USAGE) as usize;
+                let height = u32::from_str(&env::args().nth(5).ok_or(USAGE)?).expect(USAGE) as usize;
+                (width, height, BlockFormat::Bc4, input.clone())
+            }
+        };
+
+        let mut decompressed = vec![0u8; tinydxt::required_bytes(width, height, format)];
+        tinydxt::decode(&blocks, width, height, format, &mut decompressed)?;
 
         let file = File::create(output_file)?;
-        let ref mut w = std::io::BufWriter::new(file);
+        let w = &mut std::io::BufWriter::new(file);
 
-        let mut encoder = png::Encoder::new(w, width, height); // Width is 2 pixels and height is 1.
-        encoder.set_color(png::ColorType::Grayscale);
+        let mut encoder = png::Encoder::new(w, width as u32, height as u32);
+        encoder.set_color(png_color_type(format));
         encoder.set_depth(png::BitDepth::Eight);
         let mut writer = encoder.write_header()?;
         writer.write_image_data(&decompressed[..]).unwrap();
@@ -0,0 +1,23 @@
+//! Data-parallel block iteration, gated behind the `parallel` feature (which
+//! pulls in rayon). Disabled, [`for_each_chunk_mut`] falls back to a plain
+//! serial loop with the same signature, so callers don't need their own
+//! `cfg` branches and the `no_std` build is unaffected either way.
+
+#[cfg(feature = "parallel")]
+pub fn for_each_chunk_mut<F>(out: &mut [u8], chunk_size: usize, f: F)
+where
+    F: Fn(usize, &mut [u8]) + Sync + Send,
+{
+    use rayon::prelude::*;
+    out.par_chunks_mut(chunk_size).enumerate().for_each(|(i, chunk)| f(i, chunk));
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn for_each_chunk_mut<F>(out: &mut [u8], chunk_size: usize, f: F)
+where
+    F: Fn(usize, &mut [u8]),
+{
+    for (i, chunk) in out.chunks_mut(chunk_size).enumerate() {
+        f(i, chunk);
+    }
+}